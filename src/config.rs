@@ -0,0 +1,66 @@
+use std::env;
+use std::path::PathBuf;
+
+pub const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// Server configuration, resolved from (highest priority first):
+/// CLI flags, environment variables, a `.env` file, then built-in defaults.
+pub struct Config {
+    pub rpc_url: String,
+    /// Where forks are saved; persistence is off when unset
+    pub data_dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, String> {
+        // Missing .env is fine - real env vars and defaults still apply
+        dotenv::dotenv().ok();
+
+        let mut rpc_url =
+            env::var("SOLANA_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+        let mut data_dir = env::var("FORK_DATA_DIR").ok().map(PathBuf::from);
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--rpc-url" | "-u" => {
+                    rpc_url = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for {}", arg))?;
+                }
+                "--data-dir" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for {}", arg))?;
+                    data_dir = Some(PathBuf::from(value));
+                }
+                _ => {
+                    if let Some(value) = arg.strip_prefix("--rpc-url=") {
+                        rpc_url = value.to_string();
+                    } else if let Some(value) = arg.strip_prefix("--data-dir=") {
+                        data_dir = Some(PathBuf::from(value));
+                    } else {
+                        return Err(format!("Unknown argument: {}", arg));
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            rpc_url: resolve_rpc_url(&rpc_url),
+            data_dir,
+        })
+    }
+}
+
+/// Expand a cluster moniker (`mainnet-beta`, `devnet`, `testnet`, `localnet`)
+/// into its public RPC URL. Anything else is treated as a URL and kept as is.
+pub fn resolve_rpc_url(cluster_or_url: &str) -> String {
+    match cluster_or_url {
+        "mainnet-beta" | "mainnet" | "m" => DEFAULT_RPC_URL.to_string(),
+        "devnet" | "d" => "https://api.devnet.solana.com".to_string(),
+        "testnet" | "t" => "https://api.testnet.solana.com".to_string(),
+        "localnet" | "localhost" | "l" => "http://127.0.0.1:8899".to_string(),
+        url => url.to_string(),
+    }
+}
@@ -1,23 +1,68 @@
+mod config;
 mod fork_manager;
 mod rpc;
+mod storage;
+mod upstream;
 
 use axum::{
-    extract::{Path, State},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
     routing::{get, post},
     Json, Router,
 };
-use fork_manager::ForkManager;
+use config::Config;
+use fork_manager::{CreateForkOptions, ForkManager, KeepaliveOptions};
+use serde::Deserialize;
 use serde_json::{json, Value};
+use storage::ForkStore;
+
+// Program binaries go up to 10 MiB, a bit more once base64-encoded in JSON-RPC
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
 
 #[tokio::main]
 async fn main() {
-    let manager = ForkManager::new();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: solana-forking-engine [--rpc-url <cluster|url>] [--data-dir <path>]");
+            std::process::exit(1);
+        }
+    };
+
+    let mut manager = ForkManager::with_rpc_url(&config.rpc_url);
+    if let Some(data_dir) = &config.data_dir {
+        let store = match ForkStore::open(data_dir) {
+            Ok(store) => store,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        manager = manager.with_store(store);
+        if let Err(e) = manager.restore_forks().await {
+            eprintln!("⚠️  Failed to restore forks: {}", e);
+        }
+    }
 
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/fork/create", post(create_fork))
+        .route("/forks", get(list_forks))
+        .route("/fork/{fork_id}", get(get_fork).delete(delete_fork))
+        .route("/fork/load", post(load_fork))
+        .route("/fork/import", post(import_accounts))
+        .route("/replay", post(replay_in_new_fork))
+        .route("/fork/{fork_id}/keepalive", post(keepalive_fork))
+        .route("/fork/{fork_id}/save", post(save_fork))
+        .route("/fork/{fork_id}/export", post(export_accounts))
+        .route("/fork/{fork_id}/diff", get(diff_fork))
+        .route("/fork/{fork_id}/replay", post(replay_transaction))
+        .route("/fork/{fork_id}/clone", post(clone_fork))
         .route("/fork/{fork_id}/rpc", post(handle_rpc))
+        .route("/fork/{fork_id}/deploy", post(deploy_program))
         .route("/fork/{fork_id}/transactions", get(get_transaction_history))
+        .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
         .with_state(manager);
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
@@ -25,6 +70,10 @@ async fn main() {
         .unwrap();
 
     println!("🚀 Server running on http://127.0.0.1:3000");
+    println!("🌐 Default upstream: {}", config.rpc_url);
+    if let Some(data_dir) = &config.data_dir {
+        println!("💾 Saving forks to {}", data_dir.display());
+    }
 
     axum::serve(listener, app).await.unwrap();
 }
@@ -35,17 +84,242 @@ async fn health_check() -> Json<Value> {
     }))
 }
 
-// State(manager) extracts the shared ForkManager from the router
-async fn create_fork(State(manager): State<ForkManager>) -> Json<Value> {
+// State(manager) extracts the shared ForkManager from the router.
+// The JSON body is optional so a bare `curl -X POST` still works.
+async fn create_fork(
+    State(manager): State<ForkManager>,
+    options: Option<Json<CreateForkOptions>>,
+) -> Json<Value> {
     // Call the manager to create a new fork
-    let fork_id = manager.create_fork().await;
+    let result = match options {
+        Some(Json(options)) => manager.create_fork_with_options(options).await,
+        None => Ok(manager.create_fork().await),
+    };
 
-    // Return the fork ID as JSON
+    match result {
+        // Return the fork ID (and the pinned slot, if any) as JSON
+        Ok(fork_id) => {
+            let slot = manager.get_pinned_slot(&fork_id).await.ok().flatten();
+            Json(json!({
+                "fork_id": fork_id,
+                "slot": slot
+            }))
+        }
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+async fn list_forks(State(manager): State<ForkManager>) -> Json<Value> {
     Json(json!({
-        "fork_id": fork_id
+        "forks": manager.list_forks().await
     }))
 }
 
+async fn get_fork(Path(fork_id): Path<String>, State(manager): State<ForkManager>) -> Json<Value> {
+    match manager.get_fork_info(&fork_id).await {
+        Ok(info) => Json(json!(info)),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+// Like create, the body is optional; `{"ttl": ...}` replaces the fork's TTL
+async fn keepalive_fork(
+    Path(fork_id): Path<String>,
+    State(manager): State<ForkManager>,
+    options: Option<Json<KeepaliveOptions>>,
+) -> Json<Value> {
+    let Json(options) = options.unwrap_or_default();
+
+    match manager.keepalive(&fork_id, options.ttl).await {
+        Ok(info) => Json(json!(info)),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+async fn delete_fork(
+    Path(fork_id): Path<String>,
+    State(manager): State<ForkManager>,
+) -> Json<Value> {
+    match manager.delete_fork(&fork_id).await {
+        Ok(()) => Json(json!({
+            "deleted": fork_id
+        })),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+async fn save_fork(Path(fork_id): Path<String>, State(manager): State<ForkManager>) -> Json<Value> {
+    match manager.save_fork(&fork_id).await {
+        Ok(path) => Json(json!({
+            "fork_id": fork_id,
+            "path": path
+        })),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+#[derive(Deserialize)]
+struct LoadForkParams {
+    fork_id: String,
+}
+
+async fn load_fork(
+    State(manager): State<ForkManager>,
+    Json(params): Json<LoadForkParams>,
+) -> Json<Value> {
+    match manager.load_fork(&params.fork_id).await {
+        Ok(info) => Json(json!(info)),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+    /// Relative to `<data-dir>/exports`
+    dir: String,
+    /// Only these accounts (and the ProgramData of any program among them)
+    accounts: Option<Vec<String>>,
+}
+
+async fn export_accounts(
+    Path(fork_id): Path<String>,
+    State(manager): State<ForkManager>,
+    Json(params): Json<ExportParams>,
+) -> Json<Value> {
+    let result = manager
+        .export_accounts(&fork_id, &params.dir, params.accounts.as_deref())
+        .await;
+
+    match result {
+        Ok(summary) => Json(json!(summary)),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+// Accepts the same options as `/fork/create` next to `dir`
+#[derive(Deserialize)]
+struct ImportParams {
+    dir: String,
+    #[serde(flatten)]
+    options: CreateForkOptions,
+}
+
+async fn import_accounts(
+    State(manager): State<ForkManager>,
+    Json(params): Json<ImportParams>,
+) -> Json<Value> {
+    let result = manager.import_accounts(&params.dir, params.options).await;
+
+    match result {
+        Ok((fork_id, summary)) => Json(json!({
+            "fork_id": fork_id,
+            "accounts": summary.accounts,
+            "programs": summary.programs
+        })),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+async fn clone_fork(
+    Path(fork_id): Path<String>,
+    State(manager): State<ForkManager>,
+) -> Json<Value> {
+    match manager.clone_fork(&fork_id).await {
+        Ok(new_fork_id) => Json(json!({
+            "fork_id": new_fork_id,
+            "parent_fork_id": fork_id
+        })),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+#[derive(Deserialize)]
+struct DiffParams {
+    against: Option<String>,
+}
+
+// `?against=` defaults to upstream
+async fn diff_fork(
+    Path(fork_id): Path<String>,
+    Query(params): Query<DiffParams>,
+    State(manager): State<ForkManager>,
+) -> Json<Value> {
+    let against = params.against.unwrap_or_else(|| "upstream".to_string());
+
+    match manager.diff(&fork_id, &against).await {
+        Ok(accounts) => Json(json!({
+            "fork_id": fork_id,
+            "against": against,
+            "accounts": accounts
+        })),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReplayParams {
+    signature: String,
+}
+
+async fn replay_transaction(
+    Path(fork_id): Path<String>,
+    State(manager): State<ForkManager>,
+    Json(params): Json<ReplayParams>,
+) -> Json<Value> {
+    match manager
+        .replay_transaction(&fork_id, &params.signature)
+        .await
+    {
+        Ok(report) => Json(json!(report)),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
+// Accepts the same options as `/fork/create` next to `signature`
+#[derive(Deserialize)]
+struct ReplayInNewForkParams {
+    signature: String,
+    #[serde(flatten)]
+    options: CreateForkOptions,
+}
+
+async fn replay_in_new_fork(
+    State(manager): State<ForkManager>,
+    Json(params): Json<ReplayInNewForkParams>,
+) -> Json<Value> {
+    match manager
+        .replay_in_new_fork(&params.signature, params.options)
+        .await
+    {
+        Ok(report) => Json(json!(report)),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
 async fn get_transaction_history(
     Path(fork_id): Path<String>,
     State(manager): State<ForkManager>,
@@ -60,6 +334,38 @@ async fn get_transaction_history(
     }
 }
 
+#[derive(Deserialize)]
+struct DeployProgramParams {
+    program_id: Option<String>,
+    upgrade_authority: Option<String>,
+}
+
+// The raw ELF is the request body, e.g. `curl --data-binary @program.so`
+async fn deploy_program(
+    Path(fork_id): Path<String>,
+    Query(params): Query<DeployProgramParams>,
+    State(manager): State<ForkManager>,
+    elf: Bytes,
+) -> Json<Value> {
+    let result = manager
+        .deploy_program(
+            &fork_id,
+            params.program_id.as_deref(),
+            &elf,
+            params.upgrade_authority.as_deref(),
+        )
+        .await;
+
+    match result {
+        Ok(program_id) => Json(json!({
+            "program_id": program_id.to_string()
+        })),
+        Err(e) => Json(json!({
+            "error": e
+        })),
+    }
+}
+
 async fn handle_rpc(
     Path(fork_id): Path<String>,
     State(manager): State<ForkManager>,
@@ -80,21 +386,81 @@ async fn handle_rpc(
         "sendTransaction" => {
             rpc::standard::handle_send_transaction(&manager, &fork_id, &params).await
         }
+        "sendUnsignedTransaction" => {
+            rpc::standard::handle_send_unsigned_transaction(&manager, &fork_id, &params).await
+        }
+        "simulateTransaction" => {
+            rpc::standard::handle_simulate_transaction(&manager, &fork_id, &params).await
+        }
+        "getTransaction" => {
+            rpc::standard::handle_get_transaction(&manager, &fork_id, &params).await
+        }
+        "getSignatureStatuses" => {
+            rpc::standard::handle_get_signature_statuses(&manager, &fork_id, &params).await
+        }
+        "getSlot" => rpc::standard::handle_get_slot(&manager, &fork_id, &params).await,
+        "getEpochInfo" => rpc::standard::handle_get_epoch_info(&manager, &fork_id, &params).await,
+        "getBlockTime" => rpc::standard::handle_get_block_time(&manager, &fork_id, &params).await,
         "getLatestBlockhash" => {
             rpc::standard::handle_get_latest_blockhash(&manager, &fork_id, &params).await
         }
         "getAccountInfo" => {
             rpc::standard::handle_get_account_info(&manager, &fork_id, &params).await
         }
+        "getMultipleAccounts" => {
+            rpc::standard::handle_get_multiple_accounts(&manager, &fork_id, &params).await
+        }
+        "getProgramAccounts" => {
+            rpc::standard::handle_get_program_accounts(&manager, &fork_id, &params).await
+        }
+        "getTokenAccountsByOwner" => {
+            rpc::standard::handle_get_token_accounts_by_owner(&manager, &fork_id, &params).await
+        }
+        "getTokenAccountBalance" => {
+            rpc::standard::handle_get_token_account_balance(&manager, &fork_id, &params).await
+        }
+        "getTokenSupply" => {
+            rpc::standard::handle_get_token_supply(&manager, &fork_id, &params).await
+        }
+        "getTokenLargestAccounts" => {
+            rpc::standard::handle_get_token_largest_accounts(&manager, &fork_id, &params).await
+        }
         "get_token_balance" => {
             rpc::standard::handle_get_token_balance(&manager, &fork_id, &params).await
         }
 
         // Cheatcode methods
         "set_balance" => rpc::cheatcodes::handle_set_balance(&manager, &fork_id, &params).await,
+        "set_account" => rpc::cheatcodes::handle_set_account(&manager, &fork_id, &params).await,
+        "patch_account_data" => {
+            rpc::cheatcodes::handle_patch_account_data(&manager, &fork_id, &params).await
+        }
         "set_token_balance" => {
             rpc::cheatcodes::handle_set_token_balance(&manager, &fork_id, &params).await
         }
+        "create_mint" => rpc::cheatcodes::handle_create_mint(&manager, &fork_id, &params).await,
+        "set_mint_authority" => {
+            rpc::cheatcodes::handle_set_mint_authority(&manager, &fork_id, &params).await
+        }
+        "set_freeze_authority" => {
+            rpc::cheatcodes::handle_set_freeze_authority(&manager, &fork_id, &params).await
+        }
+        "set_supply" => rpc::cheatcodes::handle_set_supply(&manager, &fork_id, &params).await,
+        "set_decimals" => rpc::cheatcodes::handle_set_decimals(&manager, &fork_id, &params).await,
+        "warp_to_slot" => rpc::cheatcodes::handle_warp_to_slot(&manager, &fork_id, &params).await,
+        "set_unix_timestamp" => {
+            rpc::cheatcodes::handle_set_unix_timestamp(&manager, &fork_id, &params).await
+        }
+        "advance_time" => rpc::cheatcodes::handle_advance_time(&manager, &fork_id, &params).await,
+        "impersonate" => rpc::cheatcodes::handle_impersonate(&manager, &fork_id, &params).await,
+        "stop_impersonating" => {
+            rpc::cheatcodes::handle_stop_impersonating(&manager, &fork_id, &params).await
+        }
+        "deploy_program" => {
+            rpc::cheatcodes::handle_deploy_program(&manager, &fork_id, &params).await
+        }
+        "snapshot" => rpc::cheatcodes::handle_snapshot(&manager, &fork_id, &params).await,
+        "revert" => rpc::cheatcodes::handle_revert(&manager, &fork_id, &params).await,
 
         _ => Err(format!("Method not found: {}", method)),
     };
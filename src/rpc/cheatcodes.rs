@@ -1,5 +1,11 @@
 use crate::fork_manager::ForkManager;
+use base64::{prelude::BASE64_STANDARD, Engine};
 use serde_json::{json, Value};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::program as system_program;
+use std::str::FromStr;
 
 /// Handle set_balance cheatcode
 pub async fn handle_set_balance(
@@ -25,6 +31,90 @@ pub async fn handle_set_balance(
     Ok(json!("Success"))
 }
 
+/// Handle set_account cheatcode
+pub async fn handle_set_account(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let address = params
+        .get("address")
+        .and_then(|a| a.as_str())
+        .ok_or_else(|| "Missing address parameter".to_string())?;
+
+    let lamports = params
+        .get("lamports")
+        .and_then(|l| l.as_u64())
+        .ok_or_else(|| "Missing or invalid lamports parameter".to_string())?;
+
+    // Everything but lamports defaults to an empty system account
+    let data = match params.get("data") {
+        Some(data) => decode_base64(data, "data")?,
+        None => Vec::new(),
+    };
+
+    let owner = match params.get("owner") {
+        Some(owner) => owner
+            .as_str()
+            .ok_or_else(|| "Invalid owner parameter".to_string())
+            .and_then(|o| Pubkey::from_str(o).map_err(|e| format!("Invalid owner: {}", e)))?,
+        None => system_program::id(),
+    };
+
+    let executable = match params.get("executable") {
+        Some(executable) => executable
+            .as_bool()
+            .ok_or_else(|| "Invalid executable parameter".to_string())?,
+        None => false,
+    };
+
+    let rent_epoch = match params.get("rent_epoch") {
+        Some(rent_epoch) => rent_epoch
+            .as_u64()
+            .ok_or_else(|| "Invalid rent_epoch parameter".to_string())?,
+        None => 0,
+    };
+
+    let account = Account {
+        lamports,
+        data,
+        owner,
+        executable,
+        rent_epoch,
+    };
+    manager.set_account(fork_id, address, account).await?;
+
+    Ok(json!("Success"))
+}
+
+/// Handle patch_account_data cheatcode
+pub async fn handle_patch_account_data(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let address = params
+        .get("address")
+        .and_then(|a| a.as_str())
+        .ok_or_else(|| "Missing address parameter".to_string())?;
+
+    let offset = params
+        .get("offset")
+        .and_then(|o| o.as_u64())
+        .ok_or_else(|| "Missing or invalid offset parameter".to_string())?;
+
+    let data = params
+        .get("data")
+        .ok_or_else(|| "Missing data parameter".to_string())
+        .and_then(|data| decode_base64(data, "data"))?;
+
+    manager
+        .patch_account_data(fork_id, address, offset as usize, &data)
+        .await?;
+
+    Ok(json!("Success"))
+}
+
 pub async fn handle_set_token_balance(
     manager: &ForkManager,
     fork_id: &str,
@@ -51,3 +141,363 @@ pub async fn handle_set_token_balance(
 
     Ok(json!("Success"))
 }
+
+/// Handle create_mint cheatcode
+pub async fn handle_create_mint(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let decimals = params
+        .get("decimals")
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u8::try_from(v).ok())
+        .ok_or_else(|| "Missing or invalid decimals parameter".to_string())?;
+
+    let mint = get_optional_str(params, "mint")?;
+    let mint_authority = get_optional_str(params, "mint_authority")?;
+    let freeze_authority = get_optional_str(params, "freeze_authority")?;
+    let token_program = get_optional_str(params, "token_program")?;
+
+    let mint = manager
+        .create_mint(
+            fork_id,
+            mint,
+            decimals,
+            mint_authority,
+            freeze_authority,
+            token_program,
+        )
+        .await?;
+
+    Ok(json!(mint.to_string()))
+}
+
+/// Handle set_mint_authority cheatcode
+pub async fn handle_set_mint_authority(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let mint = get_mint(params)?;
+    let authority = get_authority(params)?;
+
+    manager.set_mint_authority(fork_id, mint, authority).await?;
+
+    Ok(json!("Success"))
+}
+
+/// Handle set_freeze_authority cheatcode
+pub async fn handle_set_freeze_authority(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let mint = get_mint(params)?;
+    let authority = get_authority(params)?;
+
+    manager
+        .set_freeze_authority(fork_id, mint, authority)
+        .await?;
+
+    Ok(json!("Success"))
+}
+
+/// Handle set_supply cheatcode
+pub async fn handle_set_supply(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let mint = get_mint(params)?;
+
+    let supply = params
+        .get("supply")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "Missing or invalid supply parameter".to_string())?;
+
+    manager.set_supply(fork_id, mint, supply).await?;
+
+    Ok(json!("Success"))
+}
+
+/// Handle set_decimals cheatcode
+pub async fn handle_set_decimals(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let mint = get_mint(params)?;
+
+    let decimals = params
+        .get("decimals")
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u8::try_from(v).ok())
+        .ok_or_else(|| "Missing or invalid decimals parameter".to_string())?;
+
+    manager.set_decimals(fork_id, mint, decimals).await?;
+
+    Ok(json!("Success"))
+}
+
+/// Handle warp_to_slot cheatcode
+pub async fn handle_warp_to_slot(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let slot = params
+        .get("slot")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "Missing or invalid slot parameter".to_string())?;
+
+    let clock = manager.warp_to_slot(fork_id, slot).await?;
+
+    Ok(clock_to_json(&clock))
+}
+
+/// Handle set_unix_timestamp cheatcode
+pub async fn handle_set_unix_timestamp(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let unix_timestamp = params
+        .get("unix_timestamp")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(|| "Missing or invalid unix_timestamp parameter".to_string())?;
+
+    let clock = manager.set_unix_timestamp(fork_id, unix_timestamp).await?;
+
+    Ok(clock_to_json(&clock))
+}
+
+/// Handle advance_time cheatcode
+pub async fn handle_advance_time(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let seconds = params
+        .get("seconds")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "Missing or invalid seconds parameter".to_string())?;
+
+    let clock = manager.advance_time(fork_id, seconds).await?;
+
+    Ok(clock_to_json(&clock))
+}
+
+/// Handle impersonate cheatcode
+pub async fn handle_impersonate(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let address = params
+        .get("address")
+        .and_then(|a| a.as_str())
+        .ok_or_else(|| "Missing address parameter".to_string())?;
+
+    manager.impersonate(fork_id, address).await?;
+
+    Ok(json!("Success"))
+}
+
+/// Handle stop_impersonating cheatcode
+pub async fn handle_stop_impersonating(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let address = params
+        .get("address")
+        .and_then(|a| a.as_str())
+        .ok_or_else(|| "Missing address parameter".to_string())?;
+
+    let was_impersonated = manager.stop_impersonating(fork_id, address).await?;
+
+    Ok(json!(was_impersonated))
+}
+
+/// Handle deploy_program cheatcode
+pub async fn handle_deploy_program(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let elf = params
+        .get("program")
+        .ok_or_else(|| "Missing program parameter".to_string())
+        .and_then(|program| decode_base64(program, "program"))?;
+
+    let program_id = get_optional_str(params, "program_id")?;
+    let upgrade_authority = get_optional_str(params, "upgrade_authority")?;
+
+    let program_id = manager
+        .deploy_program(fork_id, program_id, &elf, upgrade_authority)
+        .await?;
+
+    Ok(json!(program_id.to_string()))
+}
+
+/// Handle snapshot cheatcode
+pub async fn handle_snapshot(
+    manager: &ForkManager,
+    fork_id: &str,
+    _params: &Value,
+) -> Result<Value, String> {
+    let snapshot_id = manager.snapshot(fork_id).await?;
+
+    Ok(json!(snapshot_id))
+}
+
+/// Handle revert cheatcode
+pub async fn handle_revert(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let snapshot_id = params
+        .get("snapshot_id")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| "Missing or invalid snapshot_id parameter".to_string())?;
+
+    let reverted = manager.revert(fork_id, snapshot_id).await?;
+
+    Ok(json!(reverted))
+}
+
+fn get_mint(params: &Value) -> Result<&str, String> {
+    params
+        .get("mint")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing mint parameter".to_string())
+}
+
+// `null` removes the authority, so the parameter has to be present either way
+fn get_authority(params: &Value) -> Result<Option<&str>, String> {
+    match params.get("authority") {
+        Some(Value::Null) => Ok(None),
+        Some(Value::String(authority)) => Ok(Some(authority)),
+        _ => Err("Missing or invalid authority parameter".to_string()),
+    }
+}
+
+// Optional parameters may be left out or null, but not given another type
+fn get_optional_str<'a>(params: &'a Value, name: &str) -> Result<Option<&'a str>, String> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(format!("Invalid {} parameter: expected a string", name)),
+    }
+}
+
+fn decode_base64(value: &Value, name: &str) -> Result<Vec<u8>, String> {
+    let encoded = value
+        .as_str()
+        .ok_or_else(|| format!("Invalid {} parameter: expected a base64 string", name))?;
+    BASE64_STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid base64 {}: {}", name, e))
+}
+
+fn clock_to_json(clock: &Clock) -> Value {
+    json!({
+        "slot": clock.slot,
+        "epoch": clock.epoch,
+        "unix_timestamp": clock.unix_timestamp
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_set_account_and_patch_data() {
+        let manager = ForkManager::new();
+        let fork_id = manager.create_fork().await;
+        let address = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+
+        handle_set_account(
+            &manager,
+            &fork_id,
+            &json!({
+                "address": address.to_string(),
+                "lamports": 1_000_000,
+                "owner": owner.to_string(),
+                "data": BASE64_STANDARD.encode([1u8, 2, 3, 4, 5, 6, 7, 8]),
+                "rent_epoch": 7
+            }),
+        )
+        .await
+        .unwrap();
+
+        handle_patch_account_data(
+            &manager,
+            &fork_id,
+            &json!({
+                "address": address.to_string(),
+                "offset": 2,
+                "data": BASE64_STANDARD.encode([0xaau8, 0xbb])
+            }),
+        )
+        .await
+        .unwrap();
+
+        let account = manager
+            .get_account_info(&fork_id, &address.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.lamports, 1_000_000);
+        assert_eq!(account.owner, owner);
+        assert_eq!(account.data, vec![1, 2, 0xaa, 0xbb, 5, 6, 7, 8]);
+        assert!(!account.executable);
+        assert_eq!(account.rent_epoch, 7);
+
+        // Patches never resize the account
+        assert!(manager
+            .patch_account_data(&fork_id, &address.to_string(), 7, &[0, 0])
+            .await
+            .is_err());
+        assert!(handle_set_account(
+            &manager,
+            &fork_id,
+            &json!({ "address": address.to_string(), "lamports": 1, "data": "not base64!" }),
+        )
+        .await
+        .is_err());
+
+        println!("✓ Account set and its data patched in place");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_create_mint_rejects_invalid_params() {
+        let manager = ForkManager::new();
+        let fork_id = manager.create_fork().await;
+
+        let err = handle_create_mint(
+            &manager,
+            &fork_id,
+            &json!({ "decimals": 6, "mint_authority": 42 }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, "Invalid mint_authority parameter: expected a string");
+
+        // null is the same as leaving a parameter out
+        let mint = handle_create_mint(
+            &manager,
+            &fork_id,
+            &json!({ "decimals": 6, "freeze_authority": null }),
+        )
+        .await
+        .unwrap();
+        assert!(mint.is_string());
+
+        println!("✓ create_mint rejected a mistyped authority");
+    }
+}
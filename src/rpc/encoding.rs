@@ -0,0 +1,389 @@
+use base64::{prelude::BASE64_STANDARD, Engine};
+use litesvm::types::TransactionMetadata;
+use serde_json::json;
+use solana_account_decoder_client_types::token::{
+    real_number_string_trimmed, TokenAccountType, UiAccountState, UiMint, UiTokenAccount,
+    UiTokenAmount,
+};
+use solana_account_decoder_client_types::{
+    ParsedAccount, UiAccount, UiAccountData, UiAccountEncoding, UiDataSliceConfig,
+};
+use solana_sdk::account::Account;
+use solana_sdk::inner_instruction::InnerInstructionsList;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::program_option::COption;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status_client_types::{
+    EncodedTransaction, TransactionBinaryEncoding, UiAddressTableLookup, UiCompiledInstruction,
+    UiInnerInstructions, UiInstruction, UiMessage, UiRawMessage, UiTransaction,
+    UiTransactionEncoding, UiTransactionReturnData,
+};
+use spl_token_2022_interface::extension::{BaseState, StateWithExtensions};
+use spl_token_2022_interface::inline_spl_token;
+use spl_token_2022_interface::state::{Account as TokenAccount, AccountState, Mint};
+
+// Largest account data the RPC encodes as base58
+const MAX_BASE58_BYTES: usize = 128;
+
+/// Decode a wire-format transaction. Legacy transactions deserialize as
+/// `VersionedTransaction` too, so this accepts both.
+pub fn decode_transaction(
+    data: &str,
+    encoding: UiTransactionEncoding,
+) -> Result<VersionedTransaction, String> {
+    let bytes = match encoding {
+        UiTransactionEncoding::Base58 | UiTransactionEncoding::Binary => bs58::decode(data)
+            .into_vec()
+            .map_err(|e| format!("Error in decoding tx to base 58 : {}", e))?,
+        UiTransactionEncoding::Base64 => BASE64_STANDARD
+            .decode(data)
+            .map_err(|e| format!("Error in decoding tx to base 64 : {}", e))?,
+        other => return Err(format!("Unsupported transaction encoding: {:?}", other)),
+    };
+
+    bincode::deserialize(&bytes).map_err(|e| format!("Error in deserializing the tx : {}", e))
+}
+
+/// Encode a transaction for getTransaction. `jsonParsed` is served as plain
+/// `json`; instructions aren't parsed.
+pub fn encode_transaction(
+    tx: &VersionedTransaction,
+    encoding: UiTransactionEncoding,
+) -> Result<EncodedTransaction, String> {
+    let serialize =
+        || bincode::serialize(tx).map_err(|e| format!("Error in serializing the tx : {}", e));
+
+    Ok(match encoding {
+        UiTransactionEncoding::Binary => {
+            EncodedTransaction::LegacyBinary(bs58::encode(serialize()?).into_string())
+        }
+        UiTransactionEncoding::Base58 => EncodedTransaction::Binary(
+            bs58::encode(serialize()?).into_string(),
+            TransactionBinaryEncoding::Base58,
+        ),
+        UiTransactionEncoding::Base64 => EncodedTransaction::Binary(
+            BASE64_STANDARD.encode(serialize()?),
+            TransactionBinaryEncoding::Base64,
+        ),
+        UiTransactionEncoding::Json | UiTransactionEncoding::JsonParsed => {
+            EncodedTransaction::Json(UiTransaction {
+                signatures: tx.signatures.iter().map(ToString::to_string).collect(),
+                message: UiMessage::Raw(raw_message(&tx.message)),
+            })
+        }
+    })
+}
+
+fn raw_message(message: &VersionedMessage) -> UiRawMessage {
+    UiRawMessage {
+        header: *message.header(),
+        account_keys: message
+            .static_account_keys()
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        recent_blockhash: message.recent_blockhash().to_string(),
+        instructions: message
+            .instructions()
+            .iter()
+            .map(|instruction| UiCompiledInstruction::from(instruction, None))
+            .collect(),
+        address_table_lookups: message
+            .address_table_lookups()
+            .map(|lookups| lookups.iter().map(UiAddressTableLookup::from).collect()),
+    }
+}
+
+/// Encode an account the way the real RPC does for the given `encoding`.
+/// `jsonParsed` falls back to base64 for accounts it cannot parse, and
+/// `dataSlice` only applies to binary encodings. Token accounts are only parsed
+/// when the decimals of their mint are known (see `token_account_mint`).
+pub fn encode_account(
+    account: &Account,
+    encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+    mint_decimals: Option<u8>,
+) -> Result<UiAccount, String> {
+    let data = match encoding {
+        UiAccountEncoding::JsonParsed => match parse_account_data(account, mint_decimals) {
+            Some(parsed) => UiAccountData::Json(parsed),
+            None => UiAccountData::Binary(
+                BASE64_STANDARD.encode(slice_data(&account.data, data_slice)),
+                UiAccountEncoding::Base64,
+            ),
+        },
+        _ => encode_account_data(slice_data(&account.data, data_slice), encoding)?,
+    };
+
+    Ok(UiAccount {
+        lamports: account.lamports,
+        data,
+        owner: account.owner.to_string(),
+        executable: account.executable,
+        rent_epoch: account.rent_epoch,
+        space: Some(account.data.len() as u64),
+    })
+}
+
+fn encode_account_data(data: &[u8], encoding: UiAccountEncoding) -> Result<UiAccountData, String> {
+    // Same limit as the real RPC: base58 encoding time grows quadratically with size
+    if matches!(
+        encoding,
+        UiAccountEncoding::Binary | UiAccountEncoding::Base58
+    ) && data.len() > MAX_BASE58_BYTES
+    {
+        return Err(format!(
+            "Encoded binary (base 58) data should be less than {} bytes, please use Base64 encoding.",
+            MAX_BASE58_BYTES
+        ));
+    }
+
+    match encoding {
+        UiAccountEncoding::Binary => Ok(UiAccountData::LegacyBinary(
+            bs58::encode(data).into_string(),
+        )),
+        UiAccountEncoding::Base58 => Ok(UiAccountData::Binary(
+            bs58::encode(data).into_string(),
+            encoding,
+        )),
+        UiAccountEncoding::Base64 => Ok(UiAccountData::Binary(
+            BASE64_STANDARD.encode(data),
+            encoding,
+        )),
+        UiAccountEncoding::Base64Zstd => {
+            let compressed = zstd::encode_all(data, 0)
+                .map_err(|e| format!("Error in compressing account data : {}", e))?;
+            Ok(UiAccountData::Binary(
+                BASE64_STANDARD.encode(compressed),
+                encoding,
+            ))
+        }
+        UiAccountEncoding::JsonParsed => unreachable!("handled by encode_account"),
+    }
+}
+
+fn slice_data(data: &[u8], data_slice: Option<UiDataSliceConfig>) -> &[u8] {
+    match data_slice {
+        Some(UiDataSliceConfig { offset, length }) => {
+            let start = offset.min(data.len());
+            let end = offset.saturating_add(length).min(data.len());
+            &data[start..end]
+        }
+        None => data,
+    }
+}
+
+/// Whether `program_id` is the SPL Token or the Token-2022 program
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == inline_spl_token::id() || *program_id == spl_token_2022_interface::id()
+}
+
+// Base state of an SPL Token or Token-2022 account; Token-2022 extensions are skipped
+fn unpack_token_state<S: BaseState + Pack>(account: &Account) -> Option<S> {
+    if account.owner == spl_token_2022_interface::id() {
+        StateWithExtensions::<S>::unpack(&account.data)
+            .ok()
+            .map(|state| state.base)
+    } else if account.owner == inline_spl_token::id() && account.data.len() == S::LEN {
+        S::unpack(&account.data).ok()
+    } else {
+        None
+    }
+}
+
+pub fn unpack_token_account(account: &Account) -> Option<TokenAccount> {
+    unpack_token_state(account)
+}
+
+pub fn unpack_mint(account: &Account) -> Option<Mint> {
+    unpack_token_state(account)
+}
+
+/// Mint of an SPL token account, whose decimals are needed to parse it
+pub fn token_account_mint(account: &Account) -> Option<Pubkey> {
+    let token_account = unpack_token_account(account)?;
+    Some(token_account.mint)
+}
+
+/// Decimals of an SPL mint account
+pub fn mint_decimals(account: &Account) -> Option<u8> {
+    unpack_mint(account).map(|mint| mint.decimals)
+}
+
+/// Decoded SPL token account or mint, the `parsed` field of `jsonParsed`
+pub fn parse_token_state(
+    account: &Account,
+    mint_decimals: Option<u8>,
+) -> Option<serde_json::Value> {
+    parse_account_data(account, mint_decimals).map(|parsed| parsed.parsed)
+}
+
+// Only SPL Token and Token-2022 accounts and mints are parsed for now, without
+// their extensions
+fn parse_account_data(account: &Account, mint_decimals: Option<u8>) -> Option<ParsedAccount> {
+    let parsed = if let Some(token_account) = unpack_token_account(account) {
+        let decimals = mint_decimals?;
+        TokenAccountType::Account(UiTokenAccount {
+            mint: token_account.mint.to_string(),
+            owner: token_account.owner.to_string(),
+            token_amount: token_amount(token_account.amount, decimals),
+            delegate: coption_to_string(token_account.delegate),
+            state: match token_account.state {
+                AccountState::Uninitialized => UiAccountState::Uninitialized,
+                AccountState::Initialized => UiAccountState::Initialized,
+                AccountState::Frozen => UiAccountState::Frozen,
+            },
+            is_native: token_account.is_native.is_some(),
+            rent_exempt_reserve: Option::from(token_account.is_native)
+                .map(|reserve| token_amount(reserve, decimals)),
+            delegated_amount: (token_account.delegated_amount > 0)
+                .then(|| token_amount(token_account.delegated_amount, decimals)),
+            close_authority: coption_to_string(token_account.close_authority),
+            extensions: vec![],
+        })
+    } else if let Some(mint) = unpack_mint(account) {
+        TokenAccountType::Mint(UiMint {
+            mint_authority: coption_to_string(mint.mint_authority),
+            supply: mint.supply.to_string(),
+            decimals: mint.decimals,
+            is_initialized: mint.is_initialized,
+            freeze_authority: coption_to_string(mint.freeze_authority),
+            extensions: vec![],
+        })
+    } else {
+        return None;
+    };
+
+    Some(ParsedAccount {
+        program: if account.owner == spl_token_2022_interface::id() {
+            "spl-token-2022".to_string()
+        } else {
+            "spl-token".to_string()
+        },
+        parsed: json!(parsed),
+        space: account.data.len() as u64,
+    })
+}
+
+fn coption_to_string(value: COption<Pubkey>) -> Option<String> {
+    Option::from(value).map(|pubkey: Pubkey| pubkey.to_string())
+}
+
+pub fn token_amount(amount: u64, decimals: u8) -> UiTokenAmount {
+    UiTokenAmount {
+        ui_amount: Some(amount as f64 / 10f64.powi(decimals as i32)),
+        decimals,
+        amount: amount.to_string(),
+        ui_amount_string: real_number_string_trimmed(amount, decimals),
+    }
+}
+
+// Outer instructions without CPIs are left out, like the real RPC does
+pub fn inner_instructions(list: &InnerInstructionsList) -> Vec<UiInnerInstructions> {
+    list.iter()
+        .enumerate()
+        .filter(|(_, instructions)| !instructions.is_empty())
+        .map(|(index, instructions)| UiInnerInstructions {
+            index: index as u8,
+            instructions: instructions
+                .iter()
+                .map(|inner| {
+                    UiInstruction::Compiled(UiCompiledInstruction::from(
+                        &inner.instruction,
+                        Some(inner.stack_height as u32),
+                    ))
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn return_data(meta: &TransactionMetadata) -> Option<UiTransactionReturnData> {
+    if meta.return_data.data.is_empty() {
+        return None;
+    }
+    Some(meta.return_data.clone().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_system_interface::program as system_program;
+
+    #[test]
+    fn test_account_encodings() {
+        let mut data = vec![0u8; Mint::LEN];
+        Pack::pack(
+            Mint {
+                mint_authority: None.into(),
+                supply: 1_000_000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: None.into(),
+            },
+            &mut data,
+        )
+        .unwrap();
+        let mint = Account {
+            lamports: 1_461_600,
+            data,
+            owner: inline_spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        assert_eq!(mint_decimals(&mint), Some(6));
+
+        for encoding in [
+            UiAccountEncoding::Base58,
+            UiAccountEncoding::Base64,
+            UiAccountEncoding::Base64Zstd,
+        ] {
+            let ui_account = encode_account(&mint, encoding, None, None).unwrap();
+            assert_eq!(ui_account.data.decode().unwrap(), mint.data);
+        }
+
+        let slice = UiDataSliceConfig {
+            offset: 36,
+            length: 8,
+        };
+        let ui_account =
+            encode_account(&mint, UiAccountEncoding::Base64, Some(slice), None).unwrap();
+        assert_eq!(ui_account.data.decode().unwrap(), mint.data[36..44]);
+        assert_eq!(ui_account.space, Some(Mint::LEN as u64));
+
+        let ui_account = encode_account(&mint, UiAccountEncoding::JsonParsed, None, None).unwrap();
+        match ui_account.data {
+            UiAccountData::Json(parsed) => {
+                assert_eq!(parsed.program, "spl-token");
+                assert_eq!(parsed.parsed["type"], "mint");
+                assert_eq!(parsed.parsed["info"]["supply"], "1000000");
+                assert_eq!(parsed.parsed["info"]["decimals"], 6);
+            }
+            other => panic!("Expected parsed mint, got {:?}", other),
+        }
+
+        // Accounts that can't be parsed fall back to base64
+        let system_account = Account::new(1_000_000, 0, &system_program::id());
+        let ui_account =
+            encode_account(&system_account, UiAccountEncoding::JsonParsed, None, None).unwrap();
+        assert!(matches!(
+            ui_account.data,
+            UiAccountData::Binary(_, UiAccountEncoding::Base64)
+        ));
+
+        // Base58 is refused past 128 bytes unless sliced down
+        let large = Account::new(1_000_000, MAX_BASE58_BYTES + 1, &system_program::id());
+        for encoding in [UiAccountEncoding::Base58, UiAccountEncoding::Binary] {
+            assert!(encode_account(&large, encoding, None, None).is_err());
+        }
+        let slice = UiDataSliceConfig {
+            offset: 0,
+            length: MAX_BASE58_BYTES,
+        };
+        assert!(encode_account(&large, UiAccountEncoding::Base58, Some(slice), None).is_ok());
+
+        println!("✓ Accounts encoded as base58, base64, base64+zstd and jsonParsed");
+    }
+}
@@ -1,5 +1,3 @@
 pub mod cheatcodes;
+pub mod encoding;
 pub mod standard;
-
-pub use cheatcodes::*;
-pub use standard::*;
@@ -1,5 +1,27 @@
 use crate::fork_manager::ForkManager;
+use crate::rpc::encoding;
 use serde_json::{json, Value};
+use solana_account_decoder_client_types::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
+    RpcSignatureStatusConfig, RpcSimulateTransactionConfig, RpcTokenAccountsFilter,
+    RpcTransactionConfig,
+};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionVersion;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransactionWithStatusMeta,
+    TransactionConfirmationStatus, TransactionStatus, UiTransactionEncoding, UiTransactionError,
+    UiTransactionStatusMeta,
+};
+use std::str::FromStr;
+
+// Request size limits of the real RPC
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+const MAX_SIGNATURE_STATUSES: usize = 256;
+
 /// Handle getBalance RPC method
 pub async fn handle_get_balance(
     manager: &ForkManager,
@@ -31,13 +53,274 @@ pub async fn handle_send_transaction(
         .and_then(|v| v.as_str())
         .ok_or_else(|| "Missing transaction data parameter".to_string())?;
 
+    let config: RpcSendTransactionConfig = match params.get(1) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|e| format!("Invalid sendTransaction config: {}", e))?,
+        None => RpcSendTransactionConfig::default(),
+    };
+
+    // Legacy and v0 transactions share the same wire format
+    let tx = encoding::decode_transaction(
+        tx_data,
+        config.encoding.unwrap_or(UiTransactionEncoding::Base58),
+    )?;
+
     // Send transaction
-    let signature = manager.send_transaction(fork_id, tx_data).await?;
+    let signature = manager.send_transaction(fork_id, tx).await?;
 
     // Return signature
     Ok(json!(signature))
 }
 
+/// Handle sendUnsignedTransaction RPC method: sendTransaction for transactions
+/// missing the signatures of impersonated signers. Not part of the Solana RPC.
+pub async fn handle_send_unsigned_transaction(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let tx_data = params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing transaction data parameter".to_string())?;
+
+    let config: RpcSendTransactionConfig = match params.get(1) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|e| format!("Invalid sendUnsignedTransaction config: {}", e))?,
+        None => RpcSendTransactionConfig::default(),
+    };
+
+    let tx = encoding::decode_transaction(
+        tx_data,
+        config.encoding.unwrap_or(UiTransactionEncoding::Base58),
+    )?;
+
+    let signature = manager.send_unsigned_transaction(fork_id, tx).await?;
+
+    Ok(json!(signature))
+}
+
+/// Handle simulateTransaction RPC method
+pub async fn handle_simulate_transaction(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let tx_data = params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing transaction data parameter".to_string())?;
+
+    let config: RpcSimulateTransactionConfig = match params.get(1) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|e| format!("Invalid simulateTransaction config: {}", e))?,
+        None => RpcSimulateTransactionConfig::default(),
+    };
+
+    let tx = encoding::decode_transaction(
+        tx_data,
+        config.encoding.unwrap_or(UiTransactionEncoding::Base58),
+    )?;
+
+    let (addresses, account_encoding) = match &config.accounts {
+        Some(accounts) => {
+            let addresses = accounts
+                .addresses
+                .iter()
+                .map(|a| Pubkey::from_str(a).map_err(|e| format!("Invalid address: {}", e)))
+                .collect::<Result<Vec<_>, _>>()?;
+            (
+                Some(addresses),
+                accounts.encoding.unwrap_or(UiAccountEncoding::Base64),
+            )
+        }
+        None => (None, UiAccountEncoding::Base64),
+    };
+
+    let result = manager
+        .simulate_transaction(
+            fork_id,
+            tx,
+            config.sig_verify,
+            config.replace_recent_blockhash,
+            addresses.as_deref().unwrap_or_default(),
+        )
+        .await?;
+
+    let accounts = match addresses {
+        Some(_) => {
+            let mut accounts = Vec::with_capacity(result.accounts.len());
+            for account in &result.accounts {
+                let encoded = match account {
+                    Some(acc) => {
+                        Some(encode_account(manager, fork_id, acc, account_encoding, None).await?)
+                    }
+                    None => None,
+                };
+                accounts.push(encoded);
+            }
+            Some(accounts)
+        }
+        None => None,
+    };
+
+    let inner_instructions = config
+        .inner_instructions
+        .then(|| encoding::inner_instructions(&result.meta.inner_instructions));
+
+    let replacement_blockhash = result.replacement_blockhash.map(|blockhash| {
+        json!({
+            "blockhash": blockhash.to_string(),
+            "lastValidBlockHeight": 999999999  // Same dummy value as getLatestBlockhash
+        })
+    });
+
+    Ok(json!({
+        "context": { "slot": result.slot },
+        "value": {
+            "err": result.err.map(UiTransactionError::from),
+            "logs": result.meta.logs,
+            "accounts": accounts,
+            "unitsConsumed": result.meta.compute_units_consumed,
+            "returnData": encoding::return_data(&result.meta),
+            "innerInstructions": inner_instructions,
+            "replacementBlockhash": replacement_blockhash
+        }
+    }))
+}
+
+/// Handle getTransaction RPC method
+pub async fn handle_get_transaction(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let signature = params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing signature parameter".to_string())?;
+
+    let config: RpcTransactionConfig = match params.get(1) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|e| format!("Invalid getTransaction config: {}", e))?,
+        None => RpcTransactionConfig::default(),
+    };
+
+    let Some(record) = manager
+        .get_transactions(fork_id, &[signature.to_string()])
+        .await?
+        .pop()
+        .flatten()
+    else {
+        return Ok(Value::Null);
+    };
+
+    // Same rule as the real RPC: v0 transactions are only returned to clients that opt in
+    if let TransactionVersion::Number(version) = record.version {
+        if config
+            .max_supported_transaction_version
+            .is_none_or(|max| version > max)
+        {
+            return Err(format!(
+                "Transaction version ({}) is not supported by the requesting client. \
+                 Please try the request again with the following configuration parameter: \
+                 \"maxSupportedTransactionVersion\": {}",
+                version, version
+            ));
+        }
+    }
+
+    let transaction = encoding::encode_transaction(
+        &record.transaction,
+        config.encoding.unwrap_or(UiTransactionEncoding::Json),
+    )?;
+
+    let meta = UiTransactionStatusMeta {
+        err: record.err.clone(),
+        status: record.err.clone().map_or(Ok(()), Err),
+        fee: record.fee,
+        pre_balances: record.pre_balances,
+        post_balances: record.post_balances,
+        inner_instructions: OptionSerializer::Some(record.inner_instructions),
+        log_messages: OptionSerializer::Some(record.logs),
+        pre_token_balances: OptionSerializer::Some(record.pre_token_balances),
+        post_token_balances: OptionSerializer::Some(record.post_token_balances),
+        rewards: OptionSerializer::Some(vec![]),
+        loaded_addresses: OptionSerializer::Some(record.loaded_addresses),
+        return_data: record.return_data.into(),
+        compute_units_consumed: OptionSerializer::Some(record.compute_units_consumed),
+        cost_units: OptionSerializer::Skip,
+    };
+
+    let result = EncodedConfirmedTransactionWithStatusMeta {
+        slot: record.slot,
+        transaction: EncodedTransactionWithStatusMeta {
+            transaction,
+            meta: Some(meta),
+            version: config
+                .max_supported_transaction_version
+                .map(|_| record.version),
+        },
+        block_time: Some(record.block_time),
+    };
+
+    serde_json::to_value(result).map_err(|e| e.to_string())
+}
+
+/// Handle getSignatureStatuses RPC method. Fork transactions are final as soon
+/// as they execute.
+pub async fn handle_get_signature_statuses(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let signatures: Vec<String> = params
+        .get(0)
+        .cloned()
+        .ok_or_else(|| "Missing signatures parameter".to_string())
+        .and_then(|v| {
+            serde_json::from_value(v).map_err(|e| format!("Invalid signatures: {}", e))
+        })?;
+
+    if signatures.len() > MAX_SIGNATURE_STATUSES {
+        return Err(format!(
+            "Too many inputs provided; max {}",
+            MAX_SIGNATURE_STATUSES
+        ));
+    }
+
+    // searchTransactionHistory makes no difference: the fork keeps its whole history
+    if let Some(config) = params.get(1) {
+        serde_json::from_value::<RpcSignatureStatusConfig>(config.clone())
+            .map_err(|e| format!("Invalid getSignatureStatuses config: {}", e))?;
+    }
+
+    let statuses: Vec<Option<TransactionStatus>> = manager
+        .get_transactions(fork_id, &signatures)
+        .await?
+        .into_iter()
+        .map(|record| {
+            record.map(|record| {
+                let err = record.err.map(Into::into);
+                TransactionStatus {
+                    slot: record.slot,
+                    confirmations: None,
+                    status: err.clone().map_or(Ok(()), Err),
+                    err,
+                    confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+                }
+            })
+        })
+        .collect();
+
+    let slot = manager.get_slot(fork_id).await?;
+
+    Ok(json!({
+        "context": { "slot": slot },
+        "value": statuses
+    }))
+}
+
 pub async fn handle_get_latest_blockhash(
     manager: &ForkManager,
     fork_id: &str,
@@ -51,6 +334,44 @@ pub async fn handle_get_latest_blockhash(
     }))
 }
 
+/// Handle getSlot RPC method
+pub async fn handle_get_slot(
+    manager: &ForkManager,
+    fork_id: &str,
+    _params: &Value,
+) -> Result<Value, String> {
+    let slot = manager.get_slot(fork_id).await?;
+
+    Ok(json!(slot))
+}
+
+/// Handle getEpochInfo RPC method
+pub async fn handle_get_epoch_info(
+    manager: &ForkManager,
+    fork_id: &str,
+    _params: &Value,
+) -> Result<Value, String> {
+    let epoch_info = manager.get_epoch_info(fork_id).await?;
+
+    Ok(json!(epoch_info))
+}
+
+/// Handle getBlockTime RPC method
+pub async fn handle_get_block_time(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let slot = params
+        .get(0)
+        .and_then(|s| s.as_u64())
+        .ok_or_else(|| "Missing or invalid slot parameter".to_string())?;
+
+    let block_time = manager.get_block_time(fork_id, slot).await?;
+
+    Ok(json!(block_time))
+}
+
 pub async fn handle_get_account_info(
     manager: &ForkManager,
     fork_id: &str,
@@ -61,27 +382,340 @@ pub async fn handle_get_account_info(
         .and_then(|a| a.as_str())
         .ok_or_else(|| "Missing address parameter".to_string())?;
 
+    let config: RpcAccountInfoConfig = match params.get(1) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|e| format!("Invalid getAccountInfo config: {}", e))?,
+        None => RpcAccountInfoConfig::default(),
+    };
+
     let account = manager.get_account_info(fork_id, address).await?;
+    let slot = manager.get_slot(fork_id).await?;
 
-    match account {
-        Some(acc) => {
-            // Encode data as base58
-            let data_base58 = bs58::encode(&acc.data).into_string();
-
-            Ok(json!({
-                "value": {
-                    "lamports": acc.lamports,
-                    "owner": acc.owner.to_string(),
-                    "data": [data_base58, "base58"],
-                    "executable": acc.executable,
-                    "rentEpoch": acc.rent_epoch
-                }
-            }))
+    // Base58 stays the default so existing callers keep getting `[data, "base58"]`
+    let value = match account {
+        Some(acc) => Some(
+            encode_account(
+                manager,
+                fork_id,
+                &acc,
+                config.encoding.unwrap_or(UiAccountEncoding::Base58),
+                config.data_slice,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    Ok(json!({
+        "context": { "slot": slot },
+        "value": value
+    }))
+}
+
+/// Handle getMultipleAccounts RPC method
+pub async fn handle_get_multiple_accounts(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let addresses: Vec<String> = params
+        .get(0)
+        .cloned()
+        .ok_or_else(|| "Missing addresses parameter".to_string())
+        .and_then(|v| serde_json::from_value(v).map_err(|e| format!("Invalid addresses: {}", e)))?;
+
+    if addresses.len() > MAX_MULTIPLE_ACCOUNTS {
+        return Err(format!(
+            "Too many inputs provided; max {}",
+            MAX_MULTIPLE_ACCOUNTS
+        ));
+    }
+
+    let pubkeys = addresses
+        .iter()
+        .map(|a| Pubkey::from_str(a).map_err(|e| format!("Invalid address: {}", e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let config: RpcAccountInfoConfig = match params.get(1) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|e| format!("Invalid getMultipleAccounts config: {}", e))?,
+        None => RpcAccountInfoConfig::default(),
+    };
+    let account_encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
+
+    let accounts = manager.get_multiple_accounts(fork_id, &pubkeys).await?;
+    let slot = manager.get_slot(fork_id).await?;
+
+    let mut value = Vec::with_capacity(accounts.len());
+    for account in &accounts {
+        let encoded = match account {
+            Some(acc) => Some(
+                encode_account(manager, fork_id, acc, account_encoding, config.data_slice).await?,
+            ),
+            None => None,
+        };
+        value.push(encoded);
+    }
+
+    Ok(json!({
+        "context": { "slot": slot },
+        "value": value
+    }))
+}
+
+/// Handle getProgramAccounts RPC method. Besides the standard config it takes
+/// `"upstream"` to choose whether upstream accounts are merged in, which by
+/// default only happens for filtered queries.
+pub async fn handle_get_program_accounts(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let program_id = params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing program id parameter".to_string())?;
+    let program_id =
+        Pubkey::from_str(program_id).map_err(|e| format!("Invalid program id: {}", e))?;
+
+    let mut config = params.get(1).cloned().unwrap_or_else(|| json!({}));
+    let include_upstream = match config.as_object_mut().and_then(|c| c.remove("upstream")) {
+        Some(upstream) => Some(
+            upstream
+                .as_bool()
+                .ok_or_else(|| "Invalid upstream flag: expected a boolean".to_string())?,
+        ),
+        None => None,
+    };
+    let config: RpcProgramAccountsConfig = serde_json::from_value(config)
+        .map_err(|e| format!("Invalid getProgramAccounts config: {}", e))?;
+
+    let filters = config.filters.unwrap_or_default();
+    for filter in &filters {
+        filter
+            .verify()
+            .map_err(|e| format!("Invalid filter: {}", e))?;
+    }
+
+    // An unfiltered scan of a real program is more than most upstreams will serve
+    let include_upstream = include_upstream.unwrap_or(!filters.is_empty());
+    let accounts = manager
+        .get_program_accounts(fork_id, &program_id, &filters, include_upstream)
+        .await?;
+
+    let account_encoding = config
+        .account_config
+        .encoding
+        .unwrap_or(UiAccountEncoding::Base58);
+    let mut value = Vec::with_capacity(accounts.len());
+    for (pubkey, account) in &accounts {
+        let account = encode_account(
+            manager,
+            fork_id,
+            account,
+            account_encoding,
+            config.account_config.data_slice,
+        )
+        .await?;
+        value.push(json!({ "pubkey": pubkey.to_string(), "account": account }));
+    }
+
+    if config.with_context.unwrap_or(false) {
+        let slot = manager.get_slot(fork_id).await?;
+        Ok(json!({
+            "context": { "slot": slot },
+            "value": value
+        }))
+    } else {
+        Ok(json!(value))
+    }
+}
+
+/// Handle getTokenAccountsByOwner RPC method
+pub async fn handle_get_token_accounts_by_owner(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let owner = parse_pubkey(params.get(0), "owner")?;
+
+    let filter: RpcTokenAccountsFilter = params
+        .get(1)
+        .cloned()
+        .ok_or_else(|| "Missing mint or programId parameter".to_string())
+        .and_then(|v| {
+            serde_json::from_value(v).map_err(|e| format!("Invalid token accounts filter: {}", e))
+        })?;
+
+    let config: RpcAccountInfoConfig = match params.get(2) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|e| format!("Invalid getTokenAccountsByOwner config: {}", e))?,
+        None => RpcAccountInfoConfig::default(),
+    };
+
+    let (token_program, mint) = match filter {
+        RpcTokenAccountsFilter::Mint(mint) => {
+            let mint = parse_pubkey(Some(&json!(mint)), "mint")?;
+            let mint_account = manager
+                .get_account_info(fork_id, &mint.to_string())
+                .await?
+                .ok_or_else(|| "Invalid param: could not find mint".to_string())?;
+            if !encoding::is_token_program(&mint_account.owner) {
+                return Err("Invalid param: not a Token mint".to_string());
+            }
+            (mint_account.owner, Some(mint))
+        }
+        RpcTokenAccountsFilter::ProgramId(program_id) => {
+            let program_id = parse_pubkey(Some(&json!(program_id)), "programId")?;
+            if !encoding::is_token_program(&program_id) {
+                return Err(format!(
+                    "Invalid param: unrecognized Token program id: {}",
+                    program_id
+                ));
+            }
+            (program_id, None)
+        }
+    };
+
+    let accounts = manager
+        .get_token_accounts_by_owner(fork_id, &owner, &token_program, mint.as_ref())
+        .await?;
+    let slot = manager.get_slot(fork_id).await?;
+
+    let account_encoding = config.encoding.unwrap_or(UiAccountEncoding::Base58);
+    let mut value = Vec::with_capacity(accounts.len());
+    for (pubkey, account) in &accounts {
+        let account = encode_account(
+            manager,
+            fork_id,
+            account,
+            account_encoding,
+            config.data_slice,
+        )
+        .await?;
+        value.push(json!({ "pubkey": pubkey.to_string(), "account": account }));
+    }
+
+    Ok(json!({
+        "context": { "slot": slot },
+        "value": value
+    }))
+}
+
+/// Handle getTokenAccountBalance RPC method
+pub async fn handle_get_token_account_balance(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let pubkey = parse_pubkey(params.get(0), "token account")?;
+
+    let account = manager
+        .get_account_info(fork_id, &pubkey.to_string())
+        .await?
+        .ok_or_else(|| "Invalid param: could not find account".to_string())?;
+    let token_account = encoding::unpack_token_account(&account)
+        .ok_or_else(|| "Invalid param: not a Token account".to_string())?;
+
+    let mint = Pubkey::new_from_array(token_account.mint.to_bytes());
+    let decimals = get_mint_decimals(manager, fork_id, &mint).await?;
+    let slot = manager.get_slot(fork_id).await?;
+
+    Ok(json!({
+        "context": { "slot": slot },
+        "value": encoding::token_amount(token_account.amount, decimals)
+    }))
+}
+
+/// Handle getTokenSupply RPC method
+pub async fn handle_get_token_supply(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let pubkey = parse_pubkey(params.get(0), "mint")?;
+
+    let mint = manager
+        .get_account_info(fork_id, &pubkey.to_string())
+        .await?
+        .as_ref()
+        .and_then(encoding::unpack_mint)
+        .ok_or_else(|| "Invalid param: not a Token mint".to_string())?;
+    let slot = manager.get_slot(fork_id).await?;
+
+    Ok(json!({
+        "context": { "slot": slot },
+        "value": encoding::token_amount(mint.supply, mint.decimals)
+    }))
+}
+
+/// Handle getTokenLargestAccounts RPC method
+pub async fn handle_get_token_largest_accounts(
+    manager: &ForkManager,
+    fork_id: &str,
+    params: &Value,
+) -> Result<Value, String> {
+    let mint = parse_pubkey(params.get(0), "mint")?;
+
+    let decimals = get_mint_decimals(manager, fork_id, &mint).await?;
+    let largest = manager.get_token_largest_accounts(fork_id, &mint).await?;
+    let slot = manager.get_slot(fork_id).await?;
+
+    let value: Vec<Value> = largest
+        .into_iter()
+        .map(|(address, amount)| {
+            let mut balance = json!(encoding::token_amount(amount, decimals));
+            balance["address"] = json!(address.to_string());
+            balance
+        })
+        .collect();
+
+    Ok(json!({
+        "context": { "slot": slot },
+        "value": value
+    }))
+}
+
+fn parse_pubkey(value: Option<&Value>, name: &str) -> Result<Pubkey, String> {
+    let address = value
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("Missing {} parameter", name))?;
+    Pubkey::from_str(address).map_err(|e| format!("Invalid {} address: {}", name, e))
+}
+
+async fn get_mint_decimals(
+    manager: &ForkManager,
+    fork_id: &str,
+    mint: &Pubkey,
+) -> Result<u8, String> {
+    manager
+        .get_account_info(fork_id, &mint.to_string())
+        .await?
+        .as_ref()
+        .and_then(encoding::mint_decimals)
+        .ok_or_else(|| "Invalid param: not a Token mint".to_string())
+}
+
+// jsonParsed token accounts need their mint's decimals, which may live upstream
+async fn encode_account(
+    manager: &ForkManager,
+    fork_id: &str,
+    account: &Account,
+    account_encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+) -> Result<UiAccount, String> {
+    let mut mint_decimals = None;
+    if account_encoding == UiAccountEncoding::JsonParsed {
+        if let Some(mint) = encoding::token_account_mint(account) {
+            mint_decimals = manager
+                .get_account_info(fork_id, &mint.to_string())
+                .await?
+                .as_ref()
+                .and_then(encoding::mint_decimals);
         }
-        None => Ok(json!({
-            "value": null
-        })),
     }
+
+    encoding::encode_account(account, account_encoding, data_slice, mint_decimals)
 }
 
 pub async fn handle_get_token_balance(
@@ -103,3 +737,253 @@ pub async fn handle_get_token_balance(
 
     Ok(json!(balance))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_pack::Pack;
+    use solana_sdk::signature::{Keypair, Signer};
+    use solana_sdk::transaction::Transaction;
+    use spl_token_2022_interface::inline_spl_token;
+    use spl_token_2022_interface::state::{Account as TokenAccount, AccountState, Mint};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_transaction_and_signature_statuses() {
+        let manager = ForkManager::new();
+        let fork_id = manager.create_fork().await;
+
+        let payer = Keypair::new();
+        manager
+            .set_balance(&fork_id, &payer.pubkey().to_string(), 2_000_000_000)
+            .await
+            .unwrap();
+
+        let blockhash = manager.get_latest_blockhash(&fork_id).await.unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[solana_system_interface::instruction::transfer(
+                &payer.pubkey(),
+                &Pubkey::new_unique(),
+                1_000_000,
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            blockhash,
+        );
+        let signature = manager.send_transaction(&fork_id, tx.into()).await.unwrap();
+        let unknown = solana_sdk::signature::Signature::default().to_string();
+
+        let statuses =
+            handle_get_signature_statuses(&manager, &fork_id, &json!([[signature, unknown]]))
+                .await
+                .unwrap();
+        assert_eq!(statuses["value"][0]["confirmationStatus"], "finalized");
+        assert_eq!(statuses["value"][0]["err"], Value::Null);
+        assert_eq!(statuses["value"][1], Value::Null);
+
+        let result = handle_get_transaction(&manager, &fork_id, &json!([signature]))
+            .await
+            .unwrap();
+        assert_eq!(result["transaction"]["signatures"][0], signature.as_str());
+        assert_eq!(result["meta"]["fee"], 5_000);
+        assert_eq!(result["meta"]["status"], json!({ "Ok": null }));
+        assert!(!result["meta"]["logMessages"].as_array().unwrap().is_empty());
+        assert_eq!(result["meta"]["preBalances"][0], 2_000_000_000u64);
+
+        let result = handle_get_transaction(
+            &manager,
+            &fork_id,
+            &json!([signature, { "encoding": "base64", "maxSupportedTransactionVersion": 0 }]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["transaction"][1], "base64");
+        assert_eq!(result["version"], "legacy");
+
+        let missing = handle_get_transaction(&manager, &fork_id, &json!([unknown]))
+            .await
+            .unwrap();
+        assert_eq!(missing, Value::Null);
+
+        // Fails while the fee payer doesn't exist, then succeeds once it's funded
+        let unfunded = Keypair::new();
+        let tx = Transaction::new_signed_with_payer(
+            &[solana_system_interface::instruction::transfer(
+                &unfunded.pubkey(),
+                &Pubkey::new_unique(),
+                1_000_000,
+            )],
+            Some(&unfunded.pubkey()),
+            &[&unfunded],
+            blockhash,
+        );
+        assert!(manager
+            .send_transaction(&fork_id, tx.clone().into())
+            .await
+            .is_err());
+        manager
+            .set_balance(&fork_id, &unfunded.pubkey().to_string(), 2_000_000_000)
+            .await
+            .unwrap();
+        let signature = manager.send_transaction(&fork_id, tx.into()).await.unwrap();
+
+        let statuses = handle_get_signature_statuses(&manager, &fork_id, &json!([[signature]]))
+            .await
+            .unwrap();
+        assert_eq!(statuses["value"][0]["err"], Value::Null);
+        let result = handle_get_transaction(&manager, &fork_id, &json!([signature]))
+            .await
+            .unwrap();
+        assert_eq!(result["meta"]["status"], json!({ "Ok": null }));
+
+        println!("✓ getTransaction and getSignatureStatuses served from fork history");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_token_rpc_methods() {
+        let manager = ForkManager::new();
+        let fork_id = manager.create_fork().await;
+
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let small = Pubkey::new_unique();
+        let large = Pubkey::new_unique();
+
+        let token_program: Pubkey = inline_spl_token::id();
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Pack::pack(
+            Mint {
+                mint_authority: None.into(),
+                supply: 3_500_000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: None.into(),
+            },
+            &mut mint_data,
+        )
+        .unwrap();
+        manager
+            .set_account(
+                &fork_id,
+                &mint.to_string(),
+                Account {
+                    lamports: 1_461_600,
+                    data: mint_data,
+                    owner: token_program,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .await
+            .unwrap();
+
+        for (pubkey, amount) in [(small, 500_000), (large, 3_000_000)] {
+            let mut data = vec![0u8; TokenAccount::LEN];
+            Pack::pack(
+                TokenAccount {
+                    mint,
+                    owner,
+                    amount,
+                    state: AccountState::Initialized,
+                    ..Default::default()
+                },
+                &mut data,
+            )
+            .unwrap();
+            manager
+                .set_account(
+                    &fork_id,
+                    &pubkey.to_string(),
+                    Account {
+                        lamports: 2_039_280,
+                        data,
+                        owner: token_program,
+                        executable: false,
+                        rent_epoch: 0,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+
+        let balance =
+            handle_get_token_account_balance(&manager, &fork_id, &json!([small.to_string()]))
+                .await
+                .unwrap();
+        assert_eq!(balance["value"]["amount"], "500000");
+        assert_eq!(balance["value"]["uiAmountString"], "0.5");
+
+        let supply = handle_get_token_supply(&manager, &fork_id, &json!([mint.to_string()]))
+            .await
+            .unwrap();
+        assert_eq!(supply["value"]["amount"], "3500000");
+        assert_eq!(supply["value"]["decimals"], 6);
+
+        // The mint only exists on the fork, so upstream has nothing to add
+        let largest =
+            handle_get_token_largest_accounts(&manager, &fork_id, &json!([mint.to_string()]))
+                .await
+                .unwrap();
+        assert_eq!(largest["value"][0]["address"], large.to_string());
+        assert_eq!(largest["value"][1]["address"], small.to_string());
+        assert_eq!(largest["value"][1]["uiAmount"], 0.5);
+
+        let accounts = handle_get_token_accounts_by_owner(
+            &manager,
+            &fork_id,
+            &json!([owner.to_string(), { "mint": mint.to_string() }, { "encoding": "jsonParsed" }]),
+        )
+        .await
+        .unwrap();
+        let accounts = accounts["value"].as_array().unwrap();
+        assert_eq!(accounts.len(), 2);
+        let info = &accounts[0]["account"]["data"]["parsed"]["info"];
+        assert_eq!(info["owner"], owner.to_string());
+        assert_eq!(info["tokenAmount"]["decimals"], 6);
+
+        assert!(
+            handle_get_token_account_balance(&manager, &fork_id, &json!([mint.to_string()]))
+                .await
+                .is_err()
+        );
+
+        println!("✓ Token RPC methods answered from fork state");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_token_accounts_by_owner_without_upstream() {
+        let manager = ForkManager::with_rpc_url("http://127.0.0.1:1");
+        let fork_id = manager.create_fork().await;
+        let owner = Pubkey::new_unique();
+
+        let mint = manager
+            .create_mint(&fork_id, None, 6, None, None, None)
+            .await
+            .unwrap();
+        manager
+            .set_token_balance(&fork_id, &owner.to_string(), &mint.to_string(), 1_000_000)
+            .await
+            .unwrap();
+
+        // The unreachable upstream leaves the fork's own token accounts
+        for filter in [
+            json!({ "mint": mint.to_string() }),
+            json!({ "programId": inline_spl_token::id().to_string() }),
+        ] {
+            let accounts = handle_get_token_accounts_by_owner(
+                &manager,
+                &fork_id,
+                &json!([owner.to_string(), filter, { "encoding": "base64" }]),
+            )
+            .await
+            .unwrap();
+            let accounts = accounts["value"].as_array().unwrap();
+            assert_eq!(accounts.len(), 1);
+            assert_eq!(
+                accounts[0]["account"]["owner"],
+                inline_spl_token::id().to_string()
+            );
+        }
+
+        println!("✓ getTokenAccountsByOwner answered from the fork alone");
+    }
+}
@@ -0,0 +1,233 @@
+use crate::fork_manager::TransactionRecord;
+use crate::rpc::encoding;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder_client_types::{UiAccount, UiAccountEncoding};
+use solana_commitment_config::CommitmentLevel;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Directory of saved forks, one `<fork_id>.json` file per fork
+pub struct ForkStore {
+    dir: PathBuf,
+}
+
+/// Everything needed to rebuild a fork after a restart.
+///
+/// Snapshots taken with the `snapshot` cheatcode are not saved.
+#[derive(Serialize, Deserialize)]
+pub struct SavedFork {
+    pub fork_id: String,
+    pub created_at: String,
+    /// `None` for forks that never expire
+    pub ttl_seconds: Option<u64>,
+    /// Unix timestamp at which the fork expires
+    pub expires_at: Option<i64>,
+    pub upstream: SavedUpstream,
+    pub impersonated: Vec<String>,
+    /// Accounts deleted in the fork, which upstream reads must not bring back
+    #[serde(default)]
+    pub closed_accounts: Vec<String>,
+    /// Every account in the fork, sysvars and programs included
+    pub accounts: Vec<SavedAccount>,
+    pub transactions: Vec<SavedTransaction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedUpstream {
+    pub url: String,
+    pub commitment: CommitmentLevel,
+    pub pinned_slot: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedAccount {
+    pub pubkey: String,
+    #[serde(flatten)]
+    pub account: UiAccount,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTransaction {
+    #[serde(flatten)]
+    pub record: TransactionRecord,
+    /// Base64-encoded wire transaction, kept for getTransaction
+    pub transaction: String,
+}
+
+impl ForkStore {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create data dir {}: {}", dir.display(), e))?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    // Fork ids are UUIDs, which also keeps them from escaping the data dir
+    pub fn path(&self, fork_id: &str) -> Result<PathBuf, String> {
+        Uuid::parse_str(fork_id).map_err(|_| format!("Invalid fork id: {}", fork_id))?;
+        Ok(self.dir.join(format!("{}.json", fork_id)))
+    }
+
+    /// Directory for account files named `name` under `<data-dir>/exports`.
+    /// Absolute paths and `..` are rejected so exports can't reach outside it.
+    pub fn exports_dir(&self, name: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(name);
+        let contained = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if name.is_empty() || !contained {
+            return Err(format!(
+                "Invalid dir {}: must be a relative path without ..",
+                name
+            ));
+        }
+        Ok(self.dir.join("exports").join(relative))
+    }
+
+    // Written to a temporary file first so a crash never leaves a truncated save
+    pub fn save(&self, fork: &SavedFork) -> Result<PathBuf, String> {
+        let path = self.path(&fork.fork_id)?;
+        let tmp = self
+            .dir
+            .join(format!(".{}.{}.tmp", fork.fork_id, Uuid::new_v4()));
+
+        let json = serde_json::to_vec(fork)
+            .map_err(|e| format!("Failed to serialize fork {}: {}", fork.fork_id, e))?;
+        fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, &path).map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            format!("Failed to write {}: {}", path.display(), e)
+        })?;
+
+        Ok(path)
+    }
+
+    pub fn load(&self, fork_id: &str) -> Result<SavedFork, String> {
+        let path = self.path(fork_id)?;
+        let json = fs::read(&path).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => format!("No saved fork: {}", fork_id),
+            _ => format!("Failed to read {}: {}", path.display(), e),
+        })?;
+
+        serde_json::from_slice(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn fork_ids(&self) -> Result<Vec<String>, String> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read {}: {}", self.dir.display(), e))?;
+
+        let mut fork_ids = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    if Uuid::parse_str(stem).is_ok() {
+                        fork_ids.push(stem.to_string());
+                    }
+                }
+            }
+        }
+        Ok(fork_ids)
+    }
+
+    // Removing a fork that was never saved is not an error
+    pub fn remove(&self, fork_id: &str) -> Result<(), String> {
+        let path = self.path(fork_id)?;
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {}", path.display(), e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An account in the `solana account --output json` format, which
+/// `solana-test-validator --account` and `--account-dir` read
+#[derive(Serialize, Deserialize)]
+pub struct AccountFile {
+    pub pubkey: String,
+    pub account: UiAccount,
+}
+
+/// Contents of an account files directory
+#[derive(Default)]
+pub struct AccountFiles {
+    pub accounts: Vec<(Pubkey, Account)>,
+    /// Program ids and their binaries
+    pub programs: Vec<(Pubkey, Vec<u8>)>,
+}
+
+/// Write `<pubkey>.json` for each account and `<program_id>.so` for each program binary
+pub fn write_account_files(dir: &Path, files: &AccountFiles) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    for (pubkey, account) in &files.accounts {
+        let file = AccountFile {
+            pubkey: pubkey.to_string(),
+            account: encoding::encode_account(account, UiAccountEncoding::Base64, None, None)?,
+        };
+        let path = dir.join(format!("{}.json", pubkey));
+        let json = serde_json::to_vec_pretty(&file)
+            .map_err(|e| format!("Failed to serialize account {}: {}", pubkey, e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    for (program_id, elf) in &files.programs {
+        let path = dir.join(format!("{}.so", program_id));
+        fs::write(&path, elf).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+/// Read every `*.json` account file and `<program_id>.so` binary in `dir`.
+///
+/// Account addresses come from the file contents, as with `--account-dir`;
+/// program ids come from the `.so` file names.
+pub fn read_account_files(dir: &Path) -> Result<AccountFiles, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+    let mut files = AccountFiles::default();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let read =
+            || fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e));
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => {
+                let file: AccountFile = serde_json::from_slice(&read()?)
+                    .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+                let pubkey = Pubkey::from_str(&file.pubkey)
+                    .map_err(|e| format!("Invalid pubkey in {}: {}", path.display(), e))?;
+                let account = file
+                    .account
+                    .decode()
+                    .ok_or_else(|| format!("Unsupported account data in {}", path.display()))?;
+                files.accounts.push((pubkey, account));
+            }
+            Some("so") => {
+                let program_id = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| Pubkey::from_str(stem).ok())
+                    .ok_or_else(|| {
+                        format!("Program file name is not a program id: {}", path.display())
+                    })?;
+                files.programs.push((program_id, read()?));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
@@ -0,0 +1,194 @@
+use serde_json::json;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTokenAccountsFilter, RpcTransactionConfig,
+};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_response::{Response, RpcKeyedAccount};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding,
+};
+
+// getMultipleAccounts rejects requests for more keys than this
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Upstream cluster a fork lazily loads accounts from.
+///
+/// When a fork is pinned, every fetch passes the pinned slot as `min_context_slot`
+/// so the node refuses to answer from state older than the pin. This is a lower
+/// bound only: RPC nodes serve their current state and cannot answer at a
+/// historical slot, so accounts may reflect any slot at or after the pin.
+pub struct Upstream {
+    client: RpcClient,
+    commitment: CommitmentConfig,
+    pinned_slot: Option<u64>,
+}
+
+impl Upstream {
+    pub fn new(url: String, commitment: CommitmentConfig) -> Self {
+        Self {
+            client: RpcClient::new_with_commitment(url, commitment),
+            commitment,
+            pinned_slot: None,
+        }
+    }
+
+    // Pin to the upstream's current slot at the configured commitment
+    pub fn pin_latest(self) -> Result<Self, String> {
+        let slot = self
+            .client
+            .get_slot_with_commitment(self.commitment)
+            .map_err(|e| format!("Failed to fetch upstream slot: {}", e))?;
+        Ok(self.pin_at(slot))
+    }
+
+    pub fn pin_at(mut self, slot: u64) -> Self {
+        self.pinned_slot = Some(slot);
+        self
+    }
+
+    pub fn url(&self) -> String {
+        self.client.url()
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    pub fn pinned_slot(&self) -> Option<u64> {
+        self.pinned_slot
+    }
+
+    fn account_config(&self) -> RpcAccountInfoConfig {
+        RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64Zstd),
+            data_slice: None,
+            commitment: Some(self.commitment),
+            min_context_slot: self.pinned_slot,
+        }
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Result<Account, String> {
+        let response = self
+            .client
+            .get_account_with_config(pubkey, self.account_config())
+            .map_err(|e| e.to_string())?;
+
+        response
+            .value
+            .ok_or_else(|| format!("AccountNotFound: pubkey={}", pubkey))
+    }
+
+    // Batched lookup; results line up with `pubkeys`, `None` for nonexistent accounts
+    pub fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, String> {
+        let mut accounts = Vec::with_capacity(pubkeys.len());
+
+        for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let response = self
+                .client
+                .get_multiple_accounts_with_config(chunk, self.account_config())
+                .map_err(|e| e.to_string())?;
+            accounts.extend(response.value);
+        }
+
+        Ok(accounts)
+    }
+
+    pub fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilterType],
+    ) -> Result<Vec<(Pubkey, Account)>, String> {
+        let config = RpcProgramAccountsConfig {
+            filters: (!filters.is_empty()).then(|| filters.to_vec()),
+            account_config: self.account_config(),
+            with_context: None,
+            sort_results: None,
+        };
+
+        self.client
+            .get_program_accounts_with_config(program_id, config)
+            .map_err(|e| e.to_string())
+    }
+
+    // Served from the token owner index, which public RPCs keep even where they
+    // refuse getProgramAccounts on the token programs
+    pub fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        filter: RpcTokenAccountsFilter,
+    ) -> Result<Vec<(Pubkey, Account)>, String> {
+        let response: Response<Vec<RpcKeyedAccount>> = self
+            .client
+            .send(
+                RpcRequest::GetTokenAccountsByOwner,
+                json!([owner.to_string(), filter, self.account_config()]),
+            )
+            .map_err(|e| e.to_string())?;
+
+        response
+            .value
+            .into_iter()
+            .map(|keyed| {
+                let pubkey = keyed
+                    .pubkey
+                    .parse()
+                    .map_err(|e| format!("Invalid address from upstream: {}", e))?;
+                let account = keyed
+                    .account
+                    .decode()
+                    .ok_or_else(|| format!("Invalid account data from upstream: {}", pubkey))?;
+                Ok((pubkey, account))
+            })
+            .collect()
+    }
+
+    // getTransaction doesn't accept `processed`, so that is raised to `confirmed`
+    pub fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, String> {
+        let commitment = if self.commitment.is_at_least_confirmed() {
+            self.commitment
+        } else {
+            CommitmentConfig::confirmed()
+        };
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(commitment),
+            max_supported_transaction_version: Some(0),
+        };
+
+        self.client
+            .get_transaction_with_config(signature, config)
+            .map_err(|e| e.to_string())
+    }
+
+    // Only the addresses are used; balances are read from the fork
+    pub fn get_token_largest_accounts(&self, mint: &Pubkey) -> Result<Vec<Pubkey>, String> {
+        let response = self
+            .client
+            .get_token_largest_accounts_with_commitment(mint, self.commitment)
+            .map_err(|e| e.to_string())?;
+
+        response
+            .value
+            .iter()
+            .map(|balance| {
+                balance
+                    .address
+                    .parse()
+                    .map_err(|e| format!("Invalid address from upstream: {}", e))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,49 @@
+#!/bin/bash
+
+set -e
+
+GREEN='\033[0;32m'
+YELLOW='\033[1;33m'
+NC='\033[0m'
+
+ADDRESS="D2bJqkFEa65xFKii3dW2ByrZEitdpX3PLR9uezPoSNKi"
+
+get_balance() {
+  curl -s -X POST http://localhost:3000/fork/$1/rpc \
+    -H "Content-Type: application/json" \
+    -d "{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"getBalance\", \"params\": [\"$ADDRESS\"]}" | jq -r .result
+}
+
+set_balance() {
+  curl -s -X POST http://localhost:3000/fork/$1/rpc \
+    -H "Content-Type: application/json" \
+    -d "{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"set_balance\", \"params\": {\"address\": \"$ADDRESS\", \"lamports\": $2}}" > /dev/null
+}
+
+# Build a base scenario
+echo -e "${YELLOW}Test 1: Base scenario${NC}"
+BASE=$(curl -s -X POST http://localhost:3000/fork/create | jq -r .fork_id)
+set_balance $BASE 5000000000
+echo -e "${GREEN}✓ Base fork: $BASE${NC}\n"
+
+# Branch it
+echo -e "${YELLOW}Test 2: Clone${NC}"
+CHILD=$(curl -s -X POST http://localhost:3000/fork/$BASE/clone | jq -r .fork_id)
+BALANCE=$(get_balance $CHILD)
+if [ "$BALANCE" == "5000000000" ]; then
+    echo -e "${GREEN}✓ Clone $CHILD inherited base balance${NC}\n"
+else
+    echo -e "✗ Clone balance mismatch: $BALANCE"
+    exit 1
+fi
+
+# Diverge
+echo -e "${YELLOW}Test 3: Independence${NC}"
+set_balance $CHILD 1000000000
+BASE_BALANCE=$(get_balance $BASE)
+if [ "$BASE_BALANCE" == "5000000000" ]; then
+    echo -e "${GREEN}✓ Base fork unaffected by clone changes${NC}\n"
+else
+    echo -e "✗ Base fork changed: $BASE_BALANCE"
+    exit 1
+fi
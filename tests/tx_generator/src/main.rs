@@ -12,15 +12,18 @@ fn main() {
     let args: Vec<String> = std::env::args().collect();
     
     if args.len() < 4 {
-        eprintln!("Usage: tx_generator <blockhash> <payer_pubkey> <recipient_pubkey>");
+        eprintln!("Usage: tx_generator <blockhash> <payer_pubkey> <recipient_pubkey> [--unsigned]");
         eprintln!("\nExample:");
         eprintln!("  tx_generator 11111111111111111111111111111111 Payer... Recipient...");
+        eprintln!("\n--unsigned leaves the transaction unsigned so it can be sent as the given");
+        eprintln!("payer via sendUnsignedTransaction after impersonating it on the fork.");
         std::process::exit(1);
     }
     
     let blockhash_str = &args[1];
     let payer_str = &args[2];
     let recipient_str = &args[3];
+    let unsigned = args.get(4).is_some_and(|arg| arg == "--unsigned");
     
     // Parse inputs 
     let blockhash = Hash::from_str(blockhash_str)
@@ -30,31 +33,42 @@ fn main() {
     let recipient_pubkey = Pubkey::from_str(recipient_str)
         .expect("Invalid recipient pubkey");
     
-    // For testing, we need the payer's private key
-    // Since we can't get it from just the pubkey, we'll create a new keypair
-    // and tell the user to fund THIS address instead
-    let payer_keypair = Keypair::new();
-    
-    println!("⚠️  NOTE: Generated new keypair for transaction signing");
-    println!("📝 Payer address (use this in set_balance): {}", payer_keypair.pubkey());
+    // Signing needs the payer's private key, which we can't get from just the
+    // pubkey. Unless the payer is impersonated, create a new keypair and tell
+    // the user to fund THIS address instead
+    let payer_keypair = (!unsigned).then(Keypair::new);
+    let payer = match &payer_keypair {
+        Some(keypair) => {
+            println!("⚠️  NOTE: Generated new keypair for transaction signing");
+            println!("📝 Payer address (use this in set_balance): {}", keypair.pubkey());
+            keypair.pubkey()
+        }
+        None => {
+            println!("🎭 NOTE: Transaction left unsigned; impersonate the payer and send it with sendUnsignedTransaction");
+            println!("📝 Payer address: {}", payer_pubkey);
+            payer_pubkey
+        }
+    };
     println!("📝 Recipient address: {}", recipient_pubkey);
     println!();
     
     // Create transfer instruction (1 SOL)
     let transfer_amount = 1_000_000_000; // 1 SOL
     let instruction = system_instruction::transfer(
-        &payer_keypair.pubkey(),
+        &payer,
         &recipient_pubkey,
         transfer_amount,
     );
     
     // Create transaction
-    let message = Message::new(&[instruction], Some(&payer_keypair.pubkey()));
+    let message = Message::new(&[instruction], Some(&payer));
     let mut tx = Transaction::new_unsigned(message);
     tx.message.recent_blockhash = blockhash;
     
     // Sign transaction
-    tx.sign(&[&payer_keypair], blockhash);
+    if let Some(keypair) = &payer_keypair {
+        tx.sign(&[keypair], blockhash);
+    }
     
     // Serialize and encode
     let serialized = bincode::serialize(&tx).unwrap();
@@ -65,7 +79,7 @@ fn main() {
     println!("{}", encoded);
     println!();
     println!("💡 Transfer details:");
-    println!("   From: {}", payer_keypair.pubkey());
+    println!("   From: {}", payer);
     println!("   To:   {}", recipient_pubkey);
     println!("   Amount: {} lamports (1 SOL)", transfer_amount);
 }
\ No newline at end of file